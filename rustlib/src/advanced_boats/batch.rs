@@ -0,0 +1,289 @@
+/**
+ * Copyright (C) 2023-2024 ls4096 <ls4096@8bitbyte.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify it
+ * under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, version 3.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+ * FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for
+ * more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::simd::f64x4;
+use std::simd::StdFloat;
+use std::simd::cmp::SimdPartialOrd;
+use std::simd::num::SimdFloat;
+
+use super::boat_types::{self, BoatTypeDef};
+use super::boats;
+use super::types::Vec2;
+use super::{AdvancedBoatInputData, AdvancedBoatOutputData};
+
+// Number of boats processed per SIMD step. The remainder (count % LANES) is
+// handled one boat at a time via the scalar reference path.
+const LANES: usize = 4;
+
+
+// Processes `count` boats from `in_data`/`out_data` for the given `boat_type`, `LANES` at a
+// time. The bulk of the arithmetic (vector adds, drag/lift scaling, velocity solves) runs as
+// true SIMD lanes since the hydrodynamic/aerodynamic coefficients are shared across the whole
+// batch (all boats in one call share `boat_type`); the handful of steps that depend on a
+// per-boat angle (sin/cos, the sail force model, and the heel-angle bisection solve) fall back
+// to scalar per-lane evaluation, since `std::simd` has no vectorized transcendentals.
+pub fn boat_update_batch(boat_type: i32, in_data: &[AdvancedBoatInputData], out_data: &mut [AdvancedBoatOutputData]) -> i32 {
+    let def = match boat_types::get(boat_type) {
+        Some(def) => def,
+        None => { return -1; }, // Failure
+    };
+
+    if in_data.len() != out_data.len() {
+        return -2; // Failure
+    }
+
+    let count = in_data.len();
+    let mut i = 0;
+
+    while i + LANES <= count {
+        update_lanes(&in_data[i..i + LANES], &mut out_data[i..i + LANES], &def);
+        i += LANES;
+    }
+
+    // Scalar tail for any remainder that doesn't fill a full lane group.
+    while i < count {
+        let in_d = &in_data[i];
+        let wind_vec = Vec2::from_angle_mag(in_d.wind_angle, in_d.wind_speed);
+        let boat_vec = Vec2::from_components(in_d.boat_speed_abeam, in_d.boat_speed_ahead);
+        let current_vec = Vec2::from_angle_mag(in_d.current_angle, in_d.current_speed);
+
+        let motion = boats::calculate_boat_response(&wind_vec, &boat_vec, in_d.sail_area, &current_vec, &def);
+
+        out_data[i].boat_speed_ahead = motion.water_vec.y();
+        out_data[i].boat_speed_abeam = motion.water_vec.x();
+        out_data[i].heeling_angle = motion.heeling_angle;
+        out_data[i].heading = motion.heading();
+        out_data[i].course_over_ground = motion.cog();
+        out_data[i].speed_over_ground = motion.sog();
+        out_data[i].set = motion.set();
+
+        i += 1;
+    }
+
+    0 // Success
+}
+
+fn update_lanes(in_data: &[AdvancedBoatInputData], out_data: &mut [AdvancedBoatOutputData], def: &BoatTypeDef) {
+    let mut wind_angle = [0.0f64; LANES];
+    let mut wind_speed = [0.0f64; LANES];
+    let mut boat_abeam = [0.0f64; LANES];
+    let mut boat_ahead = [0.0f64; LANES];
+    let mut sail_area = [0.0f64; LANES];
+
+    for lane in 0..LANES {
+        wind_angle[lane] = in_data[lane].wind_angle;
+        wind_speed[lane] = in_data[lane].wind_speed;
+        boat_abeam[lane] = in_data[lane].boat_speed_abeam;
+        boat_ahead[lane] = in_data[lane].boat_speed_ahead;
+        sail_area[lane] = in_data[lane].sail_area;
+    }
+
+    // Wind components: sin/cos are evaluated per-lane since there's no vectorized
+    // transcendental in std::simd, but the rest stays in f64x4 lanes.
+    let mut wind_x = [0.0f64; LANES];
+    let mut wind_y = [0.0f64; LANES];
+    for lane in 0..LANES {
+        let wind_vec = Vec2::from_angle_mag(wind_angle[lane], wind_speed[lane]);
+        wind_x[lane] = wind_vec.x();
+        wind_y[lane] = wind_vec.y();
+    }
+
+    let boat_x = f64x4::from_array(boat_abeam);
+    let boat_y = f64x4::from_array(boat_ahead);
+    let wind_vec_apparent_x = f64x4::from_array(wind_x) + boat_x;
+    let wind_vec_apparent_y = f64x4::from_array(wind_y) + boat_y;
+
+    // Sail force and heeling angle still need per-lane angle math (the sail force
+    // model and the heel-angle bisection solve), so those stay scalar.
+    let mut f_sail_x = [0.0f64; LANES];
+    let mut f_sail_y = [0.0f64; LANES];
+    let mut heeling_angle = [0.0f64; LANES];
+    for lane in 0..LANES {
+        let wind_vec_apparent = Vec2::from_components(wind_vec_apparent_x[lane], wind_vec_apparent_y[lane]);
+        let f_sail = boats::get_f_sail(&wind_vec_apparent, sail_area[lane], &def.sail_polar);
+        heeling_angle[lane] = boats::get_heeling_angle(&f_sail, sail_area[lane], def);
+        f_sail_x[lane] = f_sail.x();
+        f_sail_y[lane] = f_sail.y();
+    }
+
+    let mut ha_cos = [0.0f64; LANES];
+    for lane in 0..LANES {
+        ha_cos[lane] = heeling_angle[lane].to_radians().cos();
+    }
+    let ha_cos = f64x4::from_array(ha_cos);
+    let ha_cos_sq = ha_cos * ha_cos;
+
+    let f_sail_x = f64x4::from_array(f_sail_x) * ha_cos_sq;
+    let f_sail_y = f64x4::from_array(f_sail_y) * ha_cos_sq;
+
+    let extra_air_area = f64x4::from_array(heeling_angle) * f64x4::splat(def.abeam_air_area_extra_per_deg_heel);
+    let wind_vec_force_x = -wind_vec_apparent_x;
+    let wind_vec_force_y = -wind_vec_apparent_y;
+
+    let f_air_x = simd_get_f(boats::AIR_DENSITY, wind_vec_force_x, def.abeam_air_drag_coefficient, f64x4::splat(def.abeam_air_area) + extra_air_area);
+    let f_air_y = simd_get_f(boats::AIR_DENSITY, wind_vec_force_y, def.ahead_air_drag_coefficient, f64x4::splat(def.ahead_air_area));
+
+    let f_aero_x = f_sail_x + f_air_x;
+    let f_aero_y = f_sail_y + f_air_y;
+
+    let v_x = simd_get_v(f_aero_x, boats::WATER_DENSITY, def.abeam_water_drag_coefficient, f64x4::splat(def.abeam_water_area) * ha_cos);
+    let v_y = simd_get_v(f_aero_y, boats::WATER_DENSITY, def.ahead_water_drag_coefficient, f64x4::splat(def.ahead_water_area));
+
+    let water_x = (boat_x + v_x) * f64x4::splat(0.5);
+    let water_y = (boat_y + v_y) * f64x4::splat(0.5);
+
+    // Current components, same per-lane sin/cos treatment as the wind above.
+    let mut current_x = [0.0f64; LANES];
+    let mut current_y = [0.0f64; LANES];
+    for lane in 0..LANES {
+        let current_vec = Vec2::from_angle_mag(in_data[lane].current_angle, in_data[lane].current_speed);
+        current_x[lane] = current_vec.x();
+        current_y[lane] = current_vec.y();
+    }
+
+    let ground_x = water_x + f64x4::from_array(current_x);
+    let ground_y = water_y + f64x4::from_array(current_y);
+
+    let water_x = water_x.to_array();
+    let water_y = water_y.to_array();
+    let ground_x = ground_x.to_array();
+    let ground_y = ground_y.to_array();
+
+    for lane in 0..LANES {
+        let water_vec = Vec2::from_components(water_x[lane], water_y[lane]);
+        let ground_vec = Vec2::from_components(ground_x[lane], ground_y[lane]);
+
+        out_data[lane].boat_speed_abeam = water_x[lane];
+        out_data[lane].boat_speed_ahead = water_y[lane];
+        out_data[lane].heeling_angle = heeling_angle[lane];
+        out_data[lane].heading = water_vec.angle();
+        out_data[lane].course_over_ground = ground_vec.angle();
+        out_data[lane].speed_over_ground = ground_vec.mag();
+        out_data[lane].set = water_vec.angle_to(&ground_vec);
+    }
+}
+
+fn simd_get_f(d: f64, v: f64x4, c: f64, a: f64x4) -> f64x4 {
+    let mag = f64x4::splat(0.5 * d * c) * v * v * a;
+    v.simd_ge(f64x4::splat(0.0)).select(mag, -mag)
+}
+
+fn simd_get_v(f: f64x4, d: f64, c: f64, a: f64x4) -> f64x4 {
+    let denom = f64x4::splat(d * c) * a;
+    let mag = ((f64x4::splat(2.0) * f / denom).abs()).sqrt();
+    f.simd_ge(f64x4::splat(0.0)).select(mag, -mag)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_input(wind_angle: f64, wind_speed: f64, boat_speed_ahead: f64, boat_speed_abeam: f64, sail_area: f64) -> AdvancedBoatInputData {
+        make_input_with_current(wind_angle, wind_speed, boat_speed_ahead, boat_speed_abeam, sail_area, 0.0, 0.0)
+    }
+
+    fn make_input_with_current(wind_angle: f64, wind_speed: f64, boat_speed_ahead: f64, boat_speed_abeam: f64, sail_area: f64, current_angle: f64, current_speed: f64) -> AdvancedBoatInputData {
+        AdvancedBoatInputData {
+            wind_angle,
+            wind_speed,
+            boat_speed_ahead,
+            boat_speed_abeam,
+            sail_area,
+            current_angle,
+            current_speed,
+        }
+    }
+
+    fn make_output() -> AdvancedBoatOutputData {
+        AdvancedBoatOutputData {
+            boat_speed_ahead: 0.0,
+            boat_speed_abeam: 0.0,
+            heeling_angle: 0.0,
+            heading: 0.0,
+            course_over_ground: 0.0,
+            speed_over_ground: 0.0,
+            set: 0.0,
+        }
+    }
+
+    #[test]
+    fn batch_matches_scalar_reference() {
+        const TOLERANCE: f64 = 0.0000001;
+
+        let inputs: Vec<AdvancedBoatInputData> = (0..10).map(|i| {
+            let a = (i as f64) * 37.0;
+            make_input_with_current(a, 5.0 + (i as f64), 1.0, 0.1 * (i as f64), 20.0, a + 90.0, 0.5)
+        }).collect();
+
+        let mut batch_out: Vec<AdvancedBoatOutputData> = (0..inputs.len()).map(|_| make_output()).collect();
+
+        assert_eq!(0, boat_update_batch(0, &inputs, &mut batch_out));
+
+        let def = boat_types::get(0).unwrap();
+        for (i, in_d) in inputs.iter().enumerate() {
+            let wind_vec = Vec2::from_angle_mag(in_d.wind_angle, in_d.wind_speed);
+            let boat_vec = Vec2::from_components(in_d.boat_speed_abeam, in_d.boat_speed_ahead);
+            let current_vec = Vec2::from_angle_mag(in_d.current_angle, in_d.current_speed);
+            let motion = boats::calculate_boat_response(&wind_vec, &boat_vec, in_d.sail_area, &current_vec, &def);
+
+            assert!((batch_out[i].boat_speed_ahead - motion.water_vec.y()).abs() < TOLERANCE);
+            assert!((batch_out[i].boat_speed_abeam - motion.water_vec.x()).abs() < TOLERANCE);
+            assert!((batch_out[i].heeling_angle - motion.heeling_angle).abs() < TOLERANCE);
+            assert!((batch_out[i].heading - motion.heading()).abs() < TOLERANCE);
+            assert!((batch_out[i].course_over_ground - motion.cog()).abs() < TOLERANCE);
+            assert!((batch_out[i].speed_over_ground - motion.sog()).abs() < TOLERANCE);
+            assert!((batch_out[i].set - motion.set()).abs() < TOLERANCE);
+        }
+    }
+
+    // Regression test for the current-vs-heading sign convention (the set() offset is
+    // positive when the current pushes the ground track to starboard of the steered
+    // heading, matching `Vec2::angle_to`'s clockwise-positive convention).
+    #[test]
+    fn set_is_positive_when_current_pushes_to_starboard() {
+        let def = boat_types::get(0).unwrap();
+
+        // A wind from dead astern keeps the boat moving dead ahead, with a current on its
+        // starboard beam. (With no wind at all, the headwind from the boat's own motion
+        // pushes the averaged velocity slightly astern, so the heading would be 180.)
+        let wind_vec = Vec2::from_components(0.0, -10.0);
+        let boat_vec = Vec2::from_components(0.0, 5.0);
+        let current_vec = Vec2::from_angle_mag(90.0, 2.0);
+
+        let motion = boats::calculate_boat_response(&wind_vec, &boat_vec, 20.0, &current_vec, &def);
+
+        assert!((motion.heading() - 0.0).abs() < 0.0001);
+        assert!(motion.cog() > 0.0 && motion.cog() < 90.0);
+        assert!(motion.set() > 0.0);
+    }
+
+    #[test]
+    fn batch_rejects_mismatched_lengths() {
+        let inputs = vec![make_input(0.0, 5.0, 0.0, 0.0, 20.0)];
+        let mut out = Vec::new();
+        assert_eq!(-2, boat_update_batch(0, &inputs, &mut out));
+    }
+
+    #[test]
+    fn batch_rejects_unknown_boat_type() {
+        let inputs = vec![make_input(0.0, 5.0, 0.0, 0.0, 20.0)];
+        let mut out = vec![make_output()];
+        // Index 1 may already be registered by the boat-type table's own test, which shares
+        // the same process-wide table, so use an index no test ever registers.
+        assert_eq!(-1, boat_update_batch(i32::MAX, &inputs, &mut out));
+    }
+}
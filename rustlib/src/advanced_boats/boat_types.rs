@@ -0,0 +1,224 @@
+/**
+ * Copyright (C) 2023-2024 ls4096 <ls4096@8bitbyte.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify it
+ * under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, version 3.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+ * FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for
+ * more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+#[cfg(feature = "std")]
+use std::sync::{OnceLock, RwLock};
+
+use super::sail_polar::SailPolar;
+
+// Per-boat-type parameters. Adding a new hull is a matter of appending a row to the
+// table below (or having the host register one at runtime) rather than adding another
+// arm to a `match boat_type`.
+#[derive(Copy, Clone)]
+pub(crate) struct BoatTypeDef {
+    pub(crate) course_change_rate: f64,
+    pub(crate) wave_effect_resistance: f64,
+    pub(crate) gust_damage_threshold: f64,
+
+    pub(crate) ahead_water_area: f64,
+    pub(crate) ahead_water_drag_coefficient: f64,
+    pub(crate) abeam_water_area: f64,
+    pub(crate) abeam_water_drag_coefficient: f64,
+
+    pub(crate) ahead_air_area: f64,
+    pub(crate) ahead_air_drag_coefficient: f64,
+    pub(crate) abeam_air_area: f64,
+    pub(crate) abeam_air_drag_coefficient: f64,
+    pub(crate) abeam_air_area_extra_per_deg_heel: f64,
+
+    // Stability (righting-arm) curve parameters: GZ(phi) = gm*sin(phi) + righting_c*sin(phi)*cos(phi)^2.
+    pub(crate) gm: f64, // Metacentric height (m), dominates GZ at small heel angles.
+    pub(crate) righting_c: f64, // Shapes GZ's fall-off at larger heel angles.
+    pub(crate) displacement: f64, // Weight (N); the righting moment is displacement*GZ(phi).
+
+    pub(crate) sail_polar: SailPolar,
+}
+
+impl BoatTypeDef {
+    // The one hull this crate originally shipped with (boat_type == 0).
+    pub(crate) fn default_hull() -> BoatTypeDef {
+        BoatTypeDef {
+            course_change_rate: 5.0,
+            wave_effect_resistance: 75.0,
+            gust_damage_threshold: 45.0 / super::KTS_IN_MPS,
+
+            ahead_water_area: 2.5, // m^2
+            ahead_water_drag_coefficient: 0.3,
+            abeam_water_area: 7.0, // m^2
+            abeam_water_drag_coefficient: 1.25,
+
+            ahead_air_area: 3.5, // m^2
+            ahead_air_drag_coefficient: 0.5,
+            abeam_air_area: 9.0, // m^2
+            abeam_air_drag_coefficient: 0.7,
+            // Extra hull exposed to the wind for every additional degree of heeling
+            abeam_air_area_extra_per_deg_heel: 0.12, // m^2/deg
+
+            gm: 1.0, // m
+            righting_c: 3.0, // m
+            displacement: 10_000.0, // N
+
+            sail_polar: SailPolar::Table,
+        }
+    }
+}
+
+// The FFI-facing representation of `BoatTypeDef`, used when the host registers or
+// overrides a boat type at runtime.
+#[repr(C)]
+pub struct BoatTypeDefFfi {
+    pub course_change_rate: f64,
+    pub wave_effect_resistance: f64,
+    pub gust_damage_threshold: f64,
+
+    pub ahead_water_area: f64,
+    pub ahead_water_drag_coefficient: f64,
+    pub abeam_water_area: f64,
+    pub abeam_water_drag_coefficient: f64,
+
+    pub ahead_air_area: f64,
+    pub ahead_air_drag_coefficient: f64,
+    pub abeam_air_area: f64,
+    pub abeam_air_drag_coefficient: f64,
+    pub abeam_air_area_extra_per_deg_heel: f64,
+
+    pub gm: f64,
+    pub righting_c: f64,
+    pub displacement: f64,
+
+    // Which `SailPolar` variant this boat type gets. 0 selects the original fixed-table
+    // rig (`SailPolar::Table`); any other value selects the classic CL/CD airfoil polar
+    // (`SailPolar::classic()`). `BoatTypeDefFfi` has no way to carry the table/polar data
+    // itself across the FFI boundary, so a host-registered boat type can only pick between
+    // the rigs this crate ships, not supply its own.
+    pub sail_rig: i32,
+}
+
+impl From<&BoatTypeDefFfi> for BoatTypeDef {
+    fn from(ffi: &BoatTypeDefFfi) -> BoatTypeDef {
+        BoatTypeDef {
+            course_change_rate: ffi.course_change_rate,
+            wave_effect_resistance: ffi.wave_effect_resistance,
+            gust_damage_threshold: ffi.gust_damage_threshold,
+
+            ahead_water_area: ffi.ahead_water_area,
+            ahead_water_drag_coefficient: ffi.ahead_water_drag_coefficient,
+            abeam_water_area: ffi.abeam_water_area,
+            abeam_water_drag_coefficient: ffi.abeam_water_drag_coefficient,
+
+            ahead_air_area: ffi.ahead_air_area,
+            ahead_air_drag_coefficient: ffi.ahead_air_drag_coefficient,
+            abeam_air_area: ffi.abeam_air_area,
+            abeam_air_drag_coefficient: ffi.abeam_air_drag_coefficient,
+            abeam_air_area_extra_per_deg_heel: ffi.abeam_air_area_extra_per_deg_heel,
+
+            gm: ffi.gm,
+            righting_c: ffi.righting_c,
+            displacement: ffi.displacement,
+
+            sail_polar: match ffi.sail_rig {
+                0 => SailPolar::Table,
+                _ => SailPolar::classic(),
+            },
+        }
+    }
+}
+
+// The runtime boat-type table below is the "allocation-heavy registry layer" this module
+// otherwise avoids: it needs `std` for `OnceLock`/`RwLock` and heap allocation for `Vec`.
+// A `no_std` embedder (e.g. a WASM build that only wants the physics) doesn't get this
+// table at all, and instead passes a `BoatTypeDefFfi` it owns to
+// `sailnavsim_advancedboats_boat_update_v_with_def`.
+
+#[cfg(feature = "std")]
+static BOAT_TYPES: OnceLock<RwLock<Vec<BoatTypeDef>>> = OnceLock::new();
+
+#[cfg(feature = "std")]
+fn table() -> &'static RwLock<Vec<BoatTypeDef>> {
+    BOAT_TYPES.get_or_init(|| RwLock::new(vec![BoatTypeDef::default_hull()]))
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn count() -> i32 {
+    table().read().unwrap().len() as i32
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn get(boat_type: i32) -> Option<BoatTypeDef> {
+    if boat_type < 0 {
+        return None;
+    }
+
+    table().read().unwrap().get(boat_type as usize).copied()
+}
+
+// Registers `def` at `boat_type`, overriding it if a def already exists at that index, or
+// appends it as a new boat type if `boat_type` is exactly the current type count. Returns
+// the index the def was stored at, or -1 if `boat_type` is out of range for either case.
+#[cfg(feature = "std")]
+pub(crate) fn register(boat_type: i32, def: BoatTypeDef) -> i32 {
+    if boat_type < 0 {
+        return -1;
+    }
+
+    let mut table = table().write().unwrap();
+    let idx = boat_type as usize;
+
+    if idx < table.len() {
+        table[idx] = def;
+        boat_type
+    } else if idx == table.len() {
+        table.push(def);
+        boat_type
+    } else {
+        -1
+    }
+}
+
+
+// `table()` is a single process-wide `OnceLock`, so these tests share it rather than each
+// getting a fresh instance. They're folded into one `#[test]` (instead of the usual one
+// test per behavior) specifically to avoid racing each other over that shared global state,
+// and stick to counts/indices relative to whatever the table already holds.
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn register_and_get_and_count_agree_on_the_shared_boat_type_table() {
+        assert!(get(0).is_some());
+        assert!(get(-1).is_none());
+
+        let before_count = count();
+
+        let mut overridden = BoatTypeDef::default_hull();
+        overridden.course_change_rate = 99.0;
+        assert_eq!(register(0, overridden), 0);
+        assert_eq!(count(), before_count);
+        assert_eq!(get(0).unwrap().course_change_rate, 99.0);
+
+        let mut appended = BoatTypeDef::default_hull();
+        appended.course_change_rate = 42.0;
+        assert_eq!(register(before_count, appended), before_count);
+        assert_eq!(count(), before_count + 1);
+        assert_eq!(get(before_count).unwrap().course_change_rate, 42.0);
+        assert!(get(before_count + 1).is_none());
+
+        let out_of_range = BoatTypeDef::default_hull();
+        assert_eq!(register(before_count + 10, out_of_range), -1);
+        assert_eq!(count(), before_count + 1);
+    }
+}
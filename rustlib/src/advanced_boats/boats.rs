@@ -14,141 +14,150 @@
  * along with this program. If not, see <https://www.gnu.org/licenses/>.
  */
 
+use super::boat_types::BoatTypeDef;
+use super::mathops;
+use super::sail_polar::SailPolar;
 use super::types::Vec2;
 
 
-// Some global constants
+// Some global constants (shared by every boat type)
 
-const WATER_DENSITY: f64 = 1_000.0; // kg/m^3
-const AIR_DENSITY: f64 = 1.204; // kg/m^3
+pub(crate) const WATER_DENSITY: f64 = 1_000.0; // kg/m^3
+pub(crate) const AIR_DENSITY: f64 = 1.204; // kg/m^3
 
 
-// Some of our "advanced boat" constants
-
-const BOAT_AHEAD_WATER_AREA: f64 = 2.5; // m^2
-const BOAT_AHEAD_WATER_DRAG_COEFFICIENT: f64 = 0.3;
-
-const BOAT_ABEAM_WATER_AREA: f64 = 7.0; // m^2
-const BOAT_ABEAM_WATER_DRAG_COEFFICIENT: f64 = 1.25;
-
-const BOAT_AHEAD_AIR_AREA: f64 = 3.5; // m^2
-const BOAT_AHEAD_AIR_DRAG_COEFFICIENT: f64 = 0.5;
+// Bundles the velocity-through-water result of `calculate_boat_response` with the
+// ground-track values obtained by folding in a current: course-over-ground and
+// speed-over-ground come from the ground velocity's `angle()`/`mag()`, and `set()` is the
+// signed heading/COG offset a navigator would read off as how far the current is setting
+// the boat off its steered course.
+pub struct BoatMotion {
+    pub water_vec: Vec2,
+    pub heeling_angle: f64,
+    pub ground_vec: Vec2,
+}
 
-const BOAT_ABEAM_AIR_AREA: f64 = 9.0; // m^2
-const BOAT_ABEAM_AIR_DRAG_COEFFICIENT: f64 = 0.7;
+impl BoatMotion {
+    // Heading: the direction the hull points and moves through the water.
+    pub fn heading(&self) -> f64 {
+        self.water_vec.angle()
+    }
 
-// Extra hull exposed to the wind for every additional degree of heeling
-const BOAT_ABEAM_AIR_AREA_EXTRA_PER_DEG_HEEL: f64 = 0.12; // m^2/deg
+    // Course over ground: the direction the boat actually tracks once current is added.
+    pub fn cog(&self) -> f64 {
+        self.ground_vec.angle()
+    }
 
-const BOAT_HEEL_RIGHTING_FORCE: f64 = 10_000.0;
+    // Speed over ground: the boat's speed along its ground track.
+    pub fn sog(&self) -> f64 {
+        self.ground_vec.mag()
+    }
 
+    // Set: the signed angle from heading to COG, i.e. how far (and which way) the current
+    // is pushing the boat's ground track off the course it's steering through the water.
+    pub fn set(&self) -> f64 {
+        self.water_vec.angle_to(&self.ground_vec)
+    }
+}
 
-pub fn calculate_boat_response(wind_vec: &Vec2, boat_vec: &Vec2, sail_area: f64) -> (Vec2, f64) {
+pub fn calculate_boat_response(wind_vec: &Vec2, boat_vec: &Vec2, sail_area: f64, current_vec: &Vec2, def: &BoatTypeDef) -> BoatMotion {
     // Apparent wind vector
     let wind_vec_apparent = wind_vec.add(&boat_vec);
 
-    // Sail force lookup
-    let f_sail = get_f_sail(&wind_vec_apparent, sail_area);
+    // Sail force, from whichever rig polar this boat type was configured with
+    let f_sail = get_f_sail(&wind_vec_apparent, sail_area, &def.sail_polar);
 
-    let heeling_angle = get_heeling_angle(&f_sail, sail_area);
+    let heeling_angle = get_heeling_angle(&f_sail, sail_area, def);
 
     // Scale sail force based on heeling angle.
     // We need to scale by the square of the cosine:
     // - one factor for the sail being at an angle to the (horizontal) wind
     // - one factor for the sail's sideways force vector being angled downward from the horizon
-    let ha_cos = heeling_angle.to_radians().cos();
+    let ha_cos = mathops::cos(heeling_angle.to_radians());
     let f_sail = f_sail.scale(ha_cos * ha_cos);
 
     // Windage force calculations (through air)
     let wind_vec_force = wind_vec_apparent.rev();
     let f_air = Vec2::from_components(
-        get_f(AIR_DENSITY, wind_vec_force.x(), BOAT_ABEAM_AIR_DRAG_COEFFICIENT, BOAT_ABEAM_AIR_AREA + BOAT_ABEAM_AIR_AREA_EXTRA_PER_DEG_HEEL * heeling_angle),
-        get_f(AIR_DENSITY, wind_vec_force.y(), BOAT_AHEAD_AIR_DRAG_COEFFICIENT, BOAT_AHEAD_AIR_AREA));
+        get_f(AIR_DENSITY, wind_vec_force.x(), def.abeam_air_drag_coefficient, def.abeam_air_area + def.abeam_air_area_extra_per_deg_heel * heeling_angle),
+        get_f(AIR_DENSITY, wind_vec_force.y(), def.ahead_air_drag_coefficient, def.ahead_air_area));
 
     // Total aerodynamic force
     let f_aero = f_sail.add(&f_air);
 
     // Velocity is computed at the point where the aerodynamic forces and hydrodynamic forces balance each other.
-    let v_x = get_v(f_aero.x(), WATER_DENSITY, BOAT_ABEAM_WATER_DRAG_COEFFICIENT, BOAT_ABEAM_WATER_AREA * heeling_angle.to_radians().cos());
-    let v_y = get_v(f_aero.y(), WATER_DENSITY, BOAT_AHEAD_WATER_DRAG_COEFFICIENT, BOAT_AHEAD_WATER_AREA);
+    let v_x = get_v(f_aero.x(), WATER_DENSITY, def.abeam_water_drag_coefficient, def.abeam_water_area * mathops::cos(heeling_angle.to_radians()));
+    let v_y = get_v(f_aero.y(), WATER_DENSITY, def.ahead_water_drag_coefficient, def.ahead_water_area);
 
     // Take the average of old boat vector and new computed vector to make the transition "smoother".
-    (Vec2::from_components((boat_vec.x() + v_x) / 2.0, (boat_vec.y() + v_y) / 2.0), heeling_angle)
-}
+    let water_vec = Vec2::from_components((boat_vec.x() + v_x) / 2.0, (boat_vec.y() + v_y) / 2.0);
 
-// The relative force that the sail provides (F_lat: abeam, F_r: ahead) in ideal trim at certain apparent wind angles
-const SAIL_RESPONSE_TABLE: [(f64, f64); 20] = [
-    (0.0, -20.0),      // 0 deg
-    (40.0, -10.0),     // 10
-    (180.0, 40.0),     // 20
-    (200.0, 120.0),    // 30
-    (180.0, 160.0),    // 40
-    (140.0, 180.0),    // 50
-    (120.0, 200.0),    // 60
-    (100.0, 210.0),    // 70
-    (80.0, 220.0),     // 80
-    (70.0, 230.0),     // 90
-    (60.0, 240.0),     // 100
-    (55.0, 250.0),     // 110
-    (50.0, 255.0),     // 120
-    (45.0, 260.0),     // 130
-    (40.0, 260.0),     // 140
-    (40.0, 255.0),     // 150
-    (45.0, 230.0),     // 160
-    (50.0, 200.0),     // 170
-    (0.0, 150.0),      // 180
-    (0.0, 0.0),        // ---
-];
-
-fn get_f_sail(wind_vec_apparent: &Vec2, sail_area: f64) -> Vec2 {
-    let mut wind_angle = wind_vec_apparent.angle();
-    let wind_mag = wind_vec_apparent.mag();
-    let mut neg_x: bool = true;
-
-    while wind_angle > 360.0 {
-        wind_angle -= 360.0;
-    }
+    // `water_vec` is velocity through the water; the current is added on top of it to get
+    // velocity over ground.
+    let ground_vec = &water_vec + current_vec;
 
-    if wind_angle > 180.0 {
-        wind_angle = 360.0 - wind_angle;
-        neg_x = false;
+    BoatMotion {
+        water_vec,
+        heeling_angle,
+        ground_vec,
     }
+}
 
-    let mut wind_angle_i = (wind_angle / 10.0) as i32;
-    let frac: f64;
-    if wind_angle_i < 0 {
-        wind_angle_i = 0;
-        frac = 0.0;
-    } else if wind_angle_i >= 18 {
-        wind_angle_i = 18;
-        frac = 0.0;
-    } else {
-        frac = (wind_angle / 10.0) - (wind_angle_i as f64);
-    }
+pub(crate) fn get_f_sail(wind_vec_apparent: &Vec2, sail_area: f64, polar: &SailPolar) -> Vec2 {
+    polar.force(wind_vec_apparent, sail_area)
+}
 
-    let (x0, y0) = SAIL_RESPONSE_TABLE[wind_angle_i as usize];
-    let (x1, y1) = SAIL_RESPONSE_TABLE[(wind_angle_i + 1) as usize];
+pub(crate) fn get_heeling_angle(f_sail: &Vec2, sail_area: f64, def: &BoatTypeDef) -> f64 {
+    // Heeling moment is a function of the sail force component abeam and
+    // the height of the center of sail force (sqrt of sail area as we are assuming a triangular sail).
+    let heeling_moment = f_sail.x().abs() * mathops::sqrt(sail_area);
 
-    let x = x0 * (1.0 - frac) + x1 * frac;
-    let y = y0 * (1.0 - frac) + y1 * frac;
+    // Equilibrium heel angle, found where the heeling moment balances the hull's
+    // righting moment: heeling_moment*cos(phi) == displacement*GZ(phi).
+    solve_heel_angle_deg(heeling_moment, def.gm, def.righting_c, def.displacement)
+}
 
-    let mut f_sail = Vec2::from_components(x, y);
+// Righting arm curve: GM dominates at small heel angles, with `c` shaping the fall-off
+// (and, past a certain heeling moment, the capsize threshold) at larger ones.
+fn gz(phi_rad: f64, gm: f64, c: f64) -> f64 {
+    let s = mathops::sin(phi_rad);
+    let cs = mathops::cos(phi_rad);
+    gm * s + c * s * cs * cs
+}
 
-    if neg_x {
-        f_sail = f_sail.flip_x();
+// Solves `heeling_moment*cos(phi) == displacement*gz(phi)` for `phi` (in degrees) over
+// [0, 90] via bisection on the residual's sign. The residual itself is not monotonic (for
+// the default hull it rises again past about 45 degrees), but it changes sign only once
+// across that range for the default hull's parameters.
+// Returns 90.0 (the edge of the model's validity) if the heeling moment overwhelms the
+// righting moment at every angle up to 90 degrees -- i.e. a capsize.
+fn solve_heel_angle_deg(heeling_moment: f64, gm: f64, c: f64, displacement: f64) -> f64 {
+    const ITERATIONS: u32 = 30;
+
+    let residual = |phi_deg: f64| -> f64 {
+        let phi_rad = phi_deg.to_radians();
+        heeling_moment * mathops::cos(phi_rad) - displacement * gz(phi_rad, gm, c)
+    };
+
+    if residual(0.0) <= 0.0 {
+        return 0.0;
+    }
+    if residual(90.0) >= 0.0 {
+        return 90.0;
     }
 
-    f_sail.scale(sail_area * wind_mag * wind_mag)
-}
-
-fn get_heeling_angle(f_sail: &Vec2, sail_area: f64) -> f64 {
-    // Heeling angle is a function of the sail force component abeam and
-    // the height of the center of sail force (sqrt of sail area as we are assuming a triangular sail).
-    let f = f_sail.x().abs() * sail_area.sqrt();
+    let mut lo = 0.0f64;
+    let mut hi = 90.0f64;
+    for _ in 0..ITERATIONS {
+        let mid = (lo + hi) / 2.0;
+        if residual(mid) > 0.0 {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
 
-    // A very rough approximation...
-    // SailForce*cos(heel) = RightingForce*sin(heel) ==> heel = atan(SailForce / RightingForce)
-    (f / BOAT_HEEL_RIGHTING_FORCE).atan().to_degrees()
+    (lo + hi) / 2.0
 }
 
 fn get_f(d: f64, v: f64, c: f64, a: f64) -> f64 {
@@ -160,7 +169,7 @@ fn get_f(d: f64, v: f64, c: f64, a: f64) -> f64 {
 
 fn get_v(f: f64, d: f64, c: f64, a: f64) -> f64 {
     match f >= 0.0 {
-        true => (2.0 * f / (d * c * a)).sqrt(),
-        false => -(-2.0 * f / (d * c * a)).sqrt(),
+        true => mathops::sqrt(2.0 * f / (d * c * a)),
+        false => -mathops::sqrt(-2.0 * f / (d * c * a)),
     }
 }
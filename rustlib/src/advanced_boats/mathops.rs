@@ -0,0 +1,60 @@
+/**
+ * Copyright (C) 2023-2024 ls4096 <ls4096@8bitbyte.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify it
+ * under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, version 3.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+ * FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for
+ * more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Thin shims over the handful of transcendental functions `types::Vec2` and `boats` need.
+// With the default `std` feature these just forward to the std `f64` methods; without it
+// (the `no_std` physics-only build), they go through `libm`, which implements the same
+// functions in pure Rust with no OS/libc dependency.
+
+#[cfg(feature = "std")]
+pub(crate) fn sin(x: f64) -> f64 {
+    x.sin()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn sin(x: f64) -> f64 {
+    libm::sin(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn cos(x: f64) -> f64 {
+    x.cos()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn cos(x: f64) -> f64 {
+    libm::cos(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn atan(x: f64) -> f64 {
+    x.atan()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn atan(x: f64) -> f64 {
+    libm::atan(x)
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn sqrt(x: f64) -> f64 {
+    x.sqrt()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn sqrt(x: f64) -> f64 {
+    libm::sqrt(x)
+}
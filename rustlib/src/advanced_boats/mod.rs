@@ -14,15 +14,27 @@
  * along with this program. If not, see <https://www.gnu.org/licenses/>.
  */
 
+#[cfg(feature = "std")]
+mod batch;
+mod boat_types;
+mod mathops;
+mod sail_polar;
 mod types;
 mod boats;
 
+use boat_types::BoatTypeDef;
+pub use boat_types::BoatTypeDefFfi;
 use types::Vec2;
 
 
 const KTS_IN_MPS: f64 = 1.943844;
 
 
+// NOTE: `current_angle`/`current_speed` here and `heading`/`course_over_ground`/
+// `speed_over_ground`/`set` below are new fields added for water-current support. Both
+// structs are `#[repr(C)]` and read/written directly by the host across the FFI boundary,
+// so this is a layout (ABI) break -- the host-side struct definitions need to grow to match
+// before taking this rustlib build.
 #[repr(C)]
 pub struct AdvancedBoatInputData {
     wind_angle: f64,
@@ -30,6 +42,8 @@ pub struct AdvancedBoatInputData {
     boat_speed_ahead: f64,
     boat_speed_abeam: f64,
     sail_area: f64,
+    current_angle: f64,
+    current_speed: f64,
 }
 
 #[repr(C)]
@@ -37,57 +51,133 @@ pub struct AdvancedBoatOutputData {
     boat_speed_ahead: f64,
     boat_speed_abeam: f64,
     heeling_angle: f64,
+    heading: f64,
+    course_over_ground: f64,
+    speed_over_ground: f64,
+    set: f64,
 }
 
 
+#[cfg(feature = "std")]
 #[no_mangle]
 pub extern fn sailnavsim_advancedboats_get_boat_type_count() -> i32 {
-    1
+    boat_types::count()
 }
 
+// Registers or overrides a `BoatTypeDef` at `boat_type`: if `boat_type` already names a
+// known type, its def is overridden in place; if `boat_type` is exactly the current type
+// count, a new type is appended. Returns the index the def ended up at, or -1 on failure
+// (e.g. `boat_type` is neither an existing index nor the next free one). Requires `std`,
+// since the boat-type table it mutates is heap-allocated.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern fn sailnavsim_advancedboats_register_boat_type(boat_type: i32, def_raw: *const BoatTypeDefFfi) -> i32 {
+    if def_raw.is_null() {
+        return -1; // Failure
+    }
+
+    let def = BoatTypeDef::from(&(*def_raw));
+    boat_types::register(boat_type, def)
+}
+
+#[cfg(feature = "std")]
 #[no_mangle]
 pub extern fn sailnavsim_advancedboats_boat_update_v(boat_type: i32, in_data_raw: *const AdvancedBoatInputData, out_data_raw: *mut AdvancedBoatOutputData) -> i32 {
     let in_data = unsafe { &(*in_data_raw) };
 
-    let wind_vec = Vec2::from_angle_mag(in_data.wind_angle, in_data.wind_speed);
-
-    let (boat_vec_out, heeling_angle) = match boat_type {
-        0 => {
-            let bv = Vec2::from_components(in_data.boat_speed_abeam, in_data.boat_speed_ahead);
-            boats::calculate_boat_response(&wind_vec, &bv, in_data.sail_area)
-        },
-        _ => { return -1; }, // Failure
+    let def = match boat_types::get(boat_type) {
+        Some(def) => def,
+        None => { return -1; }, // Failure
     };
 
+    let wind_vec = Vec2::from_angle_mag(in_data.wind_angle, in_data.wind_speed);
+    let bv = Vec2::from_components(in_data.boat_speed_abeam, in_data.boat_speed_ahead);
+    let current_vec = Vec2::from_angle_mag(in_data.current_angle, in_data.current_speed);
+    let motion = boats::calculate_boat_response(&wind_vec, &bv, in_data.sail_area, &current_vec, &def);
+
     unsafe {
-        (*out_data_raw).boat_speed_ahead = boat_vec_out.y();
-        (*out_data_raw).boat_speed_abeam = boat_vec_out.x();
-        (*out_data_raw).heeling_angle = heeling_angle;
+        (*out_data_raw).boat_speed_ahead = motion.water_vec.y();
+        (*out_data_raw).boat_speed_abeam = motion.water_vec.x();
+        (*out_data_raw).heeling_angle = motion.heeling_angle;
+        (*out_data_raw).heading = motion.heading();
+        (*out_data_raw).course_over_ground = motion.cog();
+        (*out_data_raw).speed_over_ground = motion.sog();
+        (*out_data_raw).set = motion.set();
+    }
+
+    0 // Success
+}
+
+// `no_std`-friendly equivalent of `boat_update_v` that takes the boat type's parameters
+// directly instead of looking them up in the (heap-allocated, `std`-only) boat-type table.
+// This is the entry point a constrained or WASM target should use if it only wants the
+// sail-response physics and not the allocation-heavy registry layer.
+#[no_mangle]
+pub unsafe extern fn sailnavsim_advancedboats_boat_update_v_with_def(def_raw: *const BoatTypeDefFfi, in_data_raw: *const AdvancedBoatInputData, out_data_raw: *mut AdvancedBoatOutputData) -> i32 {
+    if def_raw.is_null() {
+        return -1; // Failure
     }
 
+    let def = BoatTypeDef::from(&(*def_raw));
+    let in_data = &(*in_data_raw);
+
+    let wind_vec = Vec2::from_angle_mag(in_data.wind_angle, in_data.wind_speed);
+    let bv = Vec2::from_components(in_data.boat_speed_abeam, in_data.boat_speed_ahead);
+    let current_vec = Vec2::from_angle_mag(in_data.current_angle, in_data.current_speed);
+    let motion = boats::calculate_boat_response(&wind_vec, &bv, in_data.sail_area, &current_vec, &def);
+
+    (*out_data_raw).boat_speed_ahead = motion.water_vec.y();
+    (*out_data_raw).boat_speed_abeam = motion.water_vec.x();
+    (*out_data_raw).heeling_angle = motion.heeling_angle;
+    (*out_data_raw).heading = motion.heading();
+    (*out_data_raw).course_over_ground = motion.cog();
+    (*out_data_raw).speed_over_ground = motion.sog();
+    (*out_data_raw).set = motion.set();
+
     0 // Success
 }
 
+// Processes `count` contiguous boats from `in_data_raw`/`out_data_raw` in one FFI call
+// instead of one call per boat, which matters once a server is stepping thousands of
+// boats per tick. Internally this runs `batch::boat_update_batch`, which vectorizes the
+// update across SIMD lanes (falling back to `calculate_boat_response` for any tail that
+// doesn't fill a full lane group).
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern fn sailnavsim_advancedboats_boat_update_batch(boat_type: i32, in_data_raw: *const AdvancedBoatInputData, out_data_raw: *mut AdvancedBoatOutputData, count: usize) -> i32 {
+    if in_data_raw.is_null() || out_data_raw.is_null() {
+        return -3; // Failure
+    }
+
+    let in_data = std::slice::from_raw_parts(in_data_raw, count);
+    let out_data = std::slice::from_raw_parts_mut(out_data_raw, count);
+
+    batch::boat_update_batch(boat_type, in_data, out_data)
+}
+
+#[cfg(feature = "std")]
 #[no_mangle]
 pub extern fn sailnavsim_advancedboats_boat_course_change_rate(boat_type: i32) -> f64 {
-    match boat_type {
-        0 => 5.0,
-        _ => 0.0, // Any boat type that isn't modeled always just gets a zero rate.
+    match boat_types::get(boat_type) {
+        Some(def) => def.course_change_rate,
+        None => 0.0, // Any boat type that isn't modeled always just gets a zero rate.
     }
 }
 
+#[cfg(feature = "std")]
 #[no_mangle]
 pub extern fn sailnavsim_advancedboats_boat_wave_effect_resistance(boat_type: i32) -> f64 {
-    match boat_type {
-        0 => 75.0,
-        _ => 0.001, // Any boat type that isn't modeled just has very low wave resistance.
+    match boat_types::get(boat_type) {
+        Some(def) => def.wave_effect_resistance,
+        None => 0.001, // Any boat type that isn't modeled just has very low wave resistance.
     }
 }
 
+#[cfg(feature = "std")]
 #[no_mangle]
 pub extern fn sailnavsim_advancedboats_boat_damage_wind_gust_threshold(boat_type: i32) -> f64 {
-    match boat_type {
-        0 => 45.0 / KTS_IN_MPS,
-        _ => 0.001, // Any boat type that isn't modeled just has very low wind gust damage threshold.
+    match boat_types::get(boat_type) {
+        Some(def) => def.gust_damage_threshold,
+        None => 0.001, // Any boat type that isn't modeled just has very low wind gust damage threshold.
     }
 }
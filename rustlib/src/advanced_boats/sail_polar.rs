@@ -0,0 +1,198 @@
+/**
+ * Copyright (C) 2023-2024 ls4096 <ls4096@8bitbyte.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify it
+ * under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, version 3.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+ * FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for
+ * more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::boats::AIR_DENSITY;
+use super::mathops;
+use super::types::Vec2;
+
+const EPSILON: f64 = 0.00000001f64;
+
+// How a boat's rig turns apparent wind into a sail force. `Table` reproduces this crate's
+// original fixed-rig behavior; `ClCd` is a classic airfoil-polar model (lift perpendicular
+// to the apparent wind, drag parallel to it) that lets a boat type describe its own rig
+// instead of being stuck with the one global table.
+#[derive(Copy, Clone)]
+pub(crate) enum SailPolar {
+    Table,
+    ClCd(fn(f64) -> f64, fn(f64) -> f64),
+}
+
+impl SailPolar {
+    // A generic modern Bermudan-rig-like polar: `CL` ramps up to a stall peak around 20
+    // degrees of apparent wind angle of attack and then falls off, while `CD` keeps
+    // climbing all the way out to a dead run.
+    pub(crate) fn classic() -> SailPolar {
+        SailPolar::ClCd(classic_cl, classic_cd)
+    }
+
+    pub(crate) fn force(&self, wind_vec_apparent: &Vec2, sail_area: f64) -> Vec2 {
+        match self {
+            SailPolar::Table => table_force(wind_vec_apparent, sail_area),
+            SailPolar::ClCd(cl, cd) => clcd_force(wind_vec_apparent, sail_area, *cl, *cd),
+        }
+    }
+}
+
+// The relative force that the sail provides (F_lat: abeam, F_r: ahead) in ideal trim at certain apparent wind angles
+const SAIL_RESPONSE_TABLE: [(f64, f64); 20] = [
+    (0.0, -20.0),      // 0 deg
+    (40.0, -10.0),     // 10
+    (180.0, 40.0),     // 20
+    (200.0, 120.0),    // 30
+    (180.0, 160.0),    // 40
+    (140.0, 180.0),    // 50
+    (120.0, 200.0),    // 60
+    (100.0, 210.0),    // 70
+    (80.0, 220.0),     // 80
+    (70.0, 230.0),     // 90
+    (60.0, 240.0),     // 100
+    (55.0, 250.0),     // 110
+    (50.0, 255.0),     // 120
+    (45.0, 260.0),     // 130
+    (40.0, 260.0),     // 140
+    (40.0, 255.0),     // 150
+    (45.0, 230.0),     // 160
+    (50.0, 200.0),     // 170
+    (0.0, 150.0),      // 180
+    (0.0, 0.0),        // ---
+];
+
+fn table_force(wind_vec_apparent: &Vec2, sail_area: f64) -> Vec2 {
+    let mut wind_angle = wind_vec_apparent.angle();
+    let wind_mag = wind_vec_apparent.mag();
+    let mut neg_x: bool = true;
+
+    while wind_angle > 360.0 {
+        wind_angle -= 360.0;
+    }
+
+    if wind_angle > 180.0 {
+        wind_angle = 360.0 - wind_angle;
+        neg_x = false;
+    }
+
+    let mut wind_angle_i = (wind_angle / 10.0) as i32;
+    let frac: f64;
+    if wind_angle_i < 0 {
+        wind_angle_i = 0;
+        frac = 0.0;
+    } else if wind_angle_i >= 18 {
+        wind_angle_i = 18;
+        frac = 0.0;
+    } else {
+        frac = (wind_angle / 10.0) - (wind_angle_i as f64);
+    }
+
+    let (x0, y0) = SAIL_RESPONSE_TABLE[wind_angle_i as usize];
+    let (x1, y1) = SAIL_RESPONSE_TABLE[(wind_angle_i + 1) as usize];
+
+    let x = x0 * (1.0 - frac) + x1 * frac;
+    let y = y0 * (1.0 - frac) + y1 * frac;
+
+    let mut f_sail = Vec2::from_components(x, y);
+
+    if neg_x {
+        f_sail = f_sail.flip_x();
+    }
+
+    f_sail.scale(sail_area * wind_mag * wind_mag)
+}
+
+fn clcd_force(wind_vec_apparent: &Vec2, sail_area: f64, cl: fn(f64) -> f64, cd: fn(f64) -> f64) -> Vec2 {
+    let wind_mag_sq = wind_vec_apparent.dot(wind_vec_apparent);
+    if wind_mag_sq < EPSILON {
+        return Vec2::from_components(0.0, 0.0);
+    }
+
+    let wind_mag = mathops::sqrt(wind_mag_sq);
+    let wind_dir = wind_vec_apparent.scale(1.0 / wind_mag);
+
+    let mut alpha = wind_vec_apparent.angle();
+    while alpha > 360.0 {
+        alpha -= 360.0;
+    }
+    if alpha > 180.0 {
+        alpha = 360.0 - alpha;
+    }
+
+    let q = 0.5 * AIR_DENSITY * wind_mag * wind_mag * sail_area;
+    let lift_mag = q * cl(alpha);
+    let drag_mag = q * cd(alpha);
+
+    // Drag acts straight along the apparent wind. Lift acts perpendicular to it, on
+    // whichever side the wind is blowing from -- found via the signed `cross` against the
+    // boat's "ahead" axis.
+    let ahead = Vec2::from_components(0.0, 1.0);
+    let perp = Vec2::from_components(-wind_dir.y(), wind_dir.x());
+    let side = if ahead.cross(&wind_dir) >= 0.0 { 1.0 } else { -1.0 };
+
+    wind_dir.scale(drag_mag) + perp.scale(lift_mag * side)
+}
+
+fn classic_cl(alpha_deg: f64) -> f64 {
+    const PEAK_DEG: f64 = 20.0;
+    const PEAK_CL: f64 = 1.2;
+
+    if alpha_deg <= PEAK_DEG {
+        PEAK_CL * (alpha_deg / PEAK_DEG)
+    } else {
+        let t = ((alpha_deg - PEAK_DEG) / (180.0 - PEAK_DEG)).min(1.0);
+        PEAK_CL * (1.0 - t)
+    }
+}
+
+fn classic_cd(alpha_deg: f64) -> f64 {
+    const MIN_CD: f64 = 0.05;
+    const MAX_CD: f64 = 1.8;
+
+    let t = (alpha_deg / 180.0).max(0.0).min(1.0);
+    MIN_CD + (MAX_CD - MIN_CD) * t
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classic_cl_peaks_near_20_degrees_and_falls_off() {
+        assert!(classic_cl(20.0) > classic_cl(0.0));
+        assert!(classic_cl(20.0) > classic_cl(5.0));
+        assert!(classic_cl(20.0) > classic_cl(90.0));
+        assert!(classic_cl(20.0) > classic_cl(180.0));
+        assert!(classic_cl(180.0) >= 0.0);
+    }
+
+    #[test]
+    fn classic_cd_keeps_climbing_with_angle() {
+        assert!(classic_cd(0.0) < classic_cd(45.0));
+        assert!(classic_cd(45.0) < classic_cd(90.0));
+        assert!(classic_cd(90.0) < classic_cd(180.0));
+    }
+
+    #[test]
+    fn clcd_force_is_zero_in_dead_calm() {
+        let zero_wind = Vec2::from_components(0.0, 0.0);
+        let f = clcd_force(&zero_wind, 20.0, classic_cl, classic_cd);
+        assert_eq!(f, Vec2::from_components(0.0, 0.0));
+    }
+
+    #[test]
+    fn table_preset_matches_original_table() {
+        let wind = Vec2::from_angle_mag(35.0, 8.0);
+        assert_eq!(SailPolar::Table.force(&wind, 20.0), table_force(&wind, 20.0));
+    }
+}
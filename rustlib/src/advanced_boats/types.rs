@@ -14,7 +14,10 @@
  * along with this program. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::fmt;
+use core::fmt;
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub};
+
+use super::mathops;
 
 
 const EPSILON: f64 = 0.00000001f64;
@@ -50,7 +53,7 @@ impl Vec2 {
             }
         } else {
             // Normal angle calculation
-            let a = (self.x / self.y).atan().to_degrees();
+            let a = mathops::atan(self.x / self.y).to_degrees();
             if self.y < 0.0 {
                 a + 180.0
             } else if self.x < 0.0 {
@@ -62,25 +65,66 @@ impl Vec2 {
     }
 
     pub fn mag(&self) -> f64 {
-        ((self.x * self.x) + (self.y * self.y)).sqrt()
+        mathops::sqrt((self.x * self.x) + (self.y * self.y))
     }
 
-    pub fn add(mut self, other: &Vec2) -> Vec2 {
-        self.x = self.x + other.x;
-        self.y = self.y + other.y;
-        self
+    pub fn dot(&self, other: &Vec2) -> f64 {
+        self.x * other.x + self.y * other.y
     }
 
-    pub fn scale(mut self, scalar: f64) -> Vec2 {
-        self.x = self.x * scalar;
-        self.y = self.y * scalar;
-        self
+    // The 2D "perp-dot" product (y0*x1 - x0*y1): a scalar whose sign tells which side
+    // `other` is on relative to `self` -- positive when `other` is clockwise of `self`
+    // (e.g. to starboard, matching the `angle()` bearing convention) -- handy for signed
+    // sail-side/heel direction without going through angle math.
+    pub fn cross(&self, other: &Vec2) -> f64 {
+        self.y * other.x - self.x * other.y
     }
 
-    pub fn rev(mut self) -> Vec2 {
-        self.x = -self.x;
-        self.y = -self.y;
-        self
+    // Projects `self` onto `onto`, returning the zero vector if `onto` is too small to
+    // give a meaningful direction.
+    pub fn project_on(self, onto: &Vec2) -> Vec2 {
+        if onto.mag() < EPSILON {
+            return Vec2::from_components(0.0, 0.0);
+        }
+
+        onto.scale(self.dot(onto) / onto.dot(onto))
+    }
+
+    // Signed angle (degrees, in (-180, 180]) from `self` to `other`, positive clockwise to
+    // match the `angle()` bearing convention (e.g. a result of +10 means `other` points 10
+    // degrees to starboard of `self`). Computed from this pair's `dot`/`cross` rather than
+    // the difference of their individual `angle()`s, so it stays well-defined even when one
+    // of the vectors is too short to have a meaningful bearing of its own.
+    pub fn angle_to(&self, other: &Vec2) -> f64 {
+        let cross = self.cross(other);
+        let dot = self.dot(other);
+
+        if dot.abs() < EPSILON && cross.abs() < EPSILON {
+            return 0.0;
+        }
+
+        if dot.abs() < EPSILON {
+            return if cross > 0.0 { 90.0 } else { -90.0 };
+        }
+
+        let a = mathops::atan(cross / dot).to_degrees();
+        if dot < 0.0 {
+            if cross >= 0.0 { a + 180.0 } else { a - 180.0 }
+        } else {
+            a
+        }
+    }
+
+    pub fn add(self, other: &Vec2) -> Vec2 {
+        &self + other
+    }
+
+    pub fn scale(self, scalar: f64) -> Vec2 {
+        self * scalar
+    }
+
+    pub fn rev(self) -> Vec2 {
+        -self
     }
 
     pub fn flip_x(mut self) -> Vec2 {
@@ -98,8 +142,8 @@ impl Vec2 {
         let (angle, mag) = Vec2::normalize_angle_mag(angle, mag);
 
         Vec2 {
-            x: mag * angle.to_radians().sin(),
-            y: mag * angle.to_radians().cos(),
+            x: mag * mathops::sin(angle.to_radians()),
+            y: mag * mathops::cos(angle.to_radians()),
         }
     }
 
@@ -131,6 +175,88 @@ impl Vec2 {
     }
 }
 
+// Operator overloads, so simulation code can write ordinary vector math (`wind + boat`,
+// `-wind`, `f_sail * scalar`, `&a + &b`) instead of going through the named methods above,
+// which still exist as thin wrappers for backward compatibility.
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, rhs: Vec2) -> Vec2 {
+        &self + &rhs
+    }
+}
+
+impl Add for &Vec2 {
+    type Output = Vec2;
+
+    fn add(self, rhs: &Vec2) -> Vec2 {
+        Vec2::from_components(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, rhs: Vec2) -> Vec2 {
+        &self - &rhs
+    }
+}
+
+impl Sub for &Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, rhs: &Vec2) -> Vec2 {
+        Vec2::from_components(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<f64> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, scalar: f64) -> Vec2 {
+        &self * scalar
+    }
+}
+
+impl Mul<f64> for &Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, scalar: f64) -> Vec2 {
+        Vec2::from_components(self.x * scalar, self.y * scalar)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        -&self
+    }
+}
+
+impl Neg for &Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        Vec2::from_components(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, rhs: Vec2) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl MulAssign<f64> for Vec2 {
+    fn mul_assign(&mut self, scalar: f64) {
+        self.x *= scalar;
+        self.y *= scalar;
+    }
+}
+
 impl PartialEq for Vec2 {
     fn eq(&self, other: &Self) -> bool {
         (self.x - other.x).abs() < EPSILON && (self.y - other.y).abs() < EPSILON
@@ -397,6 +523,95 @@ mod tests {
         }
     }
 
+    #[test]
+    fn dot_cross_project() {
+        let north = Vec2::from_angle_mag(0.0, 1.0);
+        let east = Vec2::from_angle_mag(90.0, 1.0);
+        let south = Vec2::from_angle_mag(180.0, 1.0);
+        let west = Vec2::from_angle_mag(270.0, 1.0);
+
+        // Perpendicular unit vectors have zero dot product.
+        assert!(eq_f64(0.0, north.dot(&east)));
+        assert!(eq_f64(0.0, east.dot(&south)));
+
+        // Parallel unit vectors (same or opposite direction) have a dot product of +/-1.
+        assert!(eq_f64(1.0, north.dot(&north)));
+        assert!(eq_f64(-1.0, north.dot(&south)));
+
+        // The perp-dot of a vector with itself is always zero.
+        assert!(eq_f64(0.0, north.cross(&north)));
+
+        // Cross product sign tells which side the other vector is on, and swapping
+        // operands flips the sign.
+        assert!(north.cross(&east) > 0.0);
+        assert!(east.cross(&north) < 0.0);
+        assert!(eq_f64(north.cross(&east), -east.cross(&north)));
+
+        // Projecting onto a (scaled) axis keeps only the component along that axis.
+        let v = Vec2::from_angle_mag(37.0, 12.5);
+        assert_eq!(v.project_on(&north.scale(5.0)), Vec2::from_components(0.0, v.y()));
+
+        // Projecting onto a perpendicular axis yields the zero vector.
+        assert_eq!(north.project_on(&east), Vec2::from_components(0.0, 0.0));
+
+        // Projecting onto a near-zero vector also yields the zero vector.
+        let zero_ish = Vec2::from_angle_mag(12.0, 0.0);
+        assert_eq!(v.project_on(&zero_ish), Vec2::from_components(0.0, 0.0));
+    }
+
+    #[test]
+    fn angle_to() {
+        let north = Vec2::from_angle_mag(0.0, 1.0);
+        let east = Vec2::from_angle_mag(90.0, 1.0);
+        let south = Vec2::from_angle_mag(180.0, 1.0);
+        let west = Vec2::from_angle_mag(270.0, 1.0);
+
+        // A vector has zero angle to itself.
+        assert!(eq_f64(0.0, north.angle_to(&north)));
+
+        // Quarter turns, signed clockwise-positive to match the bearing convention.
+        assert!(eq_f64(90.0, north.angle_to(&east)));
+        assert!(eq_f64(-90.0, north.angle_to(&west)));
+        assert!(eq_f64(90.0, east.angle_to(&south)));
+
+        // A half turn lands on the (-180, 180] boundary.
+        assert!(eq_f64(180.0, north.angle_to(&south)));
+
+        // Result should agree with the difference of the two vectors' own bearings.
+        let a = Vec2::from_angle_mag(20.0, 3.0);
+        let b = Vec2::from_angle_mag(65.0, 1.5);
+        assert!(eq_f64(45.0, a.angle_to(&b)));
+        assert!(eq_f64(-45.0, b.angle_to(&a)));
+    }
+
+    #[test]
+    fn operator_overloads() {
+        let north = Vec2::from_angle_mag(0.0, 1.0);
+        let east = Vec2::from_angle_mag(90.0, 1.0);
+
+        // Operator overloads must agree with the named methods they wrap, for both
+        // by-value and by-reference forms.
+        assert_eq!(north.add(&east), north + east);
+        assert_eq!(&north + &east, north + east);
+
+        assert_eq!(north.add(&east.rev()), north - east);
+        assert_eq!(&north - &east, north - east);
+
+        assert_eq!(north.scale(2.5), north * 2.5);
+        assert_eq!(&north * 2.5, north * 2.5);
+
+        assert_eq!(north.rev(), -north);
+        assert_eq!(-&north, -north);
+
+        let mut v = north;
+        v += east;
+        assert_eq!(v, north + east);
+
+        let mut v = north;
+        v *= 2.5;
+        assert_eq!(v, north * 2.5);
+    }
+
     #[test]
     fn compare_from_angle_mag_with_components() {
         assert_eq!(Vec2::from_angle_mag(0.0, 0.0), Vec2::from_components(0.0, 0.0));
@@ -1,5 +1,5 @@
 /**
- * Copyright (C) 2021 ls4096 <ls4096@8bitbyte.ca>
+ * Copyright (C) 2021-2022 ls4096 <ls4096@8bitbyte.ca>
  *
  * This program is free software: you can redistribute it and/or modify it
  * under the terms of the GNU Affero General Public License as published by
@@ -14,34 +14,119 @@
  * along with this program. If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::collections::HashMap;
+use std::collections::{ HashMap, HashSet, hash_map };
+
+use std::os::raw::c_void;
 
 pub struct BoatRegistry {
-    boat_groups: HashMap<String, HashMap<String, Option<String>>>
+    boats: HashMap<String, *mut c_void>,
+    boat_groups: HashMap<String, HashMap<String, Option<String>>>,
+    // Reverse index of `boat_groups`: boat name -> set of groups it belongs to. Kept in
+    // sync alongside `boat_groups` so "which groups is boat Y in" doesn't need a scan over
+    // every group.
+    groups_by_boat: HashMap<String, HashSet<String>>,
+}
+
+pub struct BoatRegistryIter<'a> {
+    iter: hash_map::Iter<'a, String, *mut c_void>,
+    count: usize,
+    at: usize,
+}
+
+impl BoatRegistryIter<'_> {
+    pub fn has_next(&self) -> bool {
+        self.at < self.count
+    }
+
+    pub fn next(&mut self) -> *mut c_void {
+        self.at += 1;
+        match self.iter.next() {
+            Some(b) => *(b.1),
+            None => 0 as *mut c_void,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
 }
 
 impl BoatRegistry {
     pub fn new() -> BoatRegistry {
         BoatRegistry {
-            boat_groups: HashMap::new()
+            boats: HashMap::new(),
+            boat_groups: HashMap::new(),
+            groups_by_boat: HashMap::new(),
+        }
+    }
+
+    pub fn add_boat(&mut self, boat_entry: *mut c_void, boat_name: String) -> bool {
+        match self.boats.get(&boat_name) {
+            Some(_) => false,
+            None => {
+                self.boats.insert(boat_name, boat_entry);
+                true
+            }
+        }
+    }
+
+    pub fn get_boat(&mut self, boat_name: &String) -> *mut c_void {
+        match self.boats.get_mut(boat_name) {
+            Some(b) => *b,
+            None => 0 as *mut c_void,
+        }
+    }
+
+    // Removes the boat from the main registry and, since a boat that's gone shouldn't
+    // still show up in group membership queries, from every group it was a member of.
+    pub fn remove_boat(&mut self, boat_name: &String) -> *mut c_void {
+        let removed = self.boats.remove(boat_name);
+
+        if let Some(groups) = self.groups_by_boat.remove(boat_name) {
+            for group in groups.iter() {
+                if let Some(boat_group) = self.boat_groups.get_mut(group) {
+                    boat_group.remove(boat_name);
+                    if boat_group.len() == 0 {
+                        self.boat_groups.remove(group);
+                    }
+                }
+            }
+        }
+
+        match removed {
+            Some(b) => b,
+            None => 0 as *mut c_void,
+        }
+    }
+
+    pub fn get_boats_iterator(&self) -> BoatRegistryIter {
+        BoatRegistryIter {
+            iter: self.boats.iter(),
+            count: self.boats.len(),
+            at: 0,
         }
     }
 
+
     pub fn add_boat_to_group(&mut self, group: String, boat: String, boat_altname: Option<String>) -> bool {
-        match self.boat_groups.get_mut(&group) {
+        let result = match self.boat_groups.get_mut(&group) {
             Some(boat_group) => {
-                match boat_group.insert(boat, boat_altname) {
+                match boat_group.insert(boat.clone(), boat_altname) {
                     Some(_) => false,
                     None => true,
                 }
             }
             None => {
                 let mut boat_group = HashMap::new();
-                boat_group.insert(boat, boat_altname);
-                self.boat_groups.insert(group, boat_group);
+                boat_group.insert(boat.clone(), boat_altname);
+                self.boat_groups.insert(group.clone(), boat_group);
                 true
             }
-        }
+        };
+
+        self.groups_by_boat.entry(boat).or_insert_with(HashSet::new).insert(group);
+
+        result
     }
 
     pub fn remove_boat_from_group(&mut self, group: &String, boat: &String) {
@@ -56,6 +141,13 @@ impl BoatRegistry {
                 // Group not found, so nothing to do.
             }
         }
+
+        if let Some(groups) = self.groups_by_boat.get_mut(boat) {
+            groups.remove(group);
+            if groups.len() == 0 {
+                self.groups_by_boat.remove(boat);
+            }
+        }
     }
 
     pub fn produce_group_membership_response(&self, group: &String) -> String {
@@ -78,4 +170,251 @@ impl BoatRegistry {
         }
         resp
     }
+
+    // Mirrors `produce_group_membership_response`, but answers "which groups is this boat
+    // in" via the reverse index instead of "which boats are in this group".
+    pub fn produce_boat_groups_response(&self, boat: &String) -> String {
+        let mut resp = String::from("");
+        match self.groups_by_boat.get(boat) {
+            Some(groups) => {
+                for group in groups.iter() {
+                    resp.push_str(group);
+                    resp.push_str("\n");
+                }
+            },
+            None => {
+                // Boat not found in any group, so nothing to do.
+            }
+        }
+        resp
+    }
+
+    /// Serializes the whole `boat_groups` topology (group name -> {boat name -> optional
+    /// altname}) into a compact, length-prefixed binary blob so a host can snapshot and
+    /// later restore fleet/group state across a process restart. Boat pointers are
+    /// deliberately not part of this format; they're reconstructed by the host re-adding
+    /// boats after a restore.
+    ///
+    /// Format: u32 group count, then per group: u32-prefixed UTF-8 name, u32 member count,
+    /// then per member: u32-prefixed UTF-8 boat name, and a u32-prefixed UTF-8 altname
+    /// (length 0xFFFFFFFF is the sentinel for `None`, to disambiguate from an empty string).
+    pub fn serialize_groups(&self) -> Vec<u8> {
+        const NONE_SENTINEL: u32 = 0xFFFFFFFF;
+
+        let mut buf = Vec::new();
+
+        buf.extend_from_slice(&(self.boat_groups.len() as u32).to_le_bytes());
+
+        for (group, boats) in self.boat_groups.iter() {
+            write_str(&mut buf, group);
+            buf.extend_from_slice(&(boats.len() as u32).to_le_bytes());
+
+            for (boat, altname) in boats.iter() {
+                write_str(&mut buf, boat);
+                match altname {
+                    Some(an) => write_str(&mut buf, an),
+                    None => buf.extend_from_slice(&NONE_SENTINEL.to_le_bytes()),
+                }
+            }
+        }
+
+        buf
+    }
+
+    /// Restores the `boat_groups` topology (and its `groups_by_boat` reverse index) from a
+    /// blob produced by `serialize_groups`, replacing whatever topology is currently held.
+    /// Returns `false` (leaving the registry's group topology untouched) if the blob is
+    /// truncated or malformed.
+    pub fn deserialize_groups(&mut self, data: &[u8]) -> bool {
+        const NONE_SENTINEL: u32 = 0xFFFFFFFF;
+
+        let mut pos = 0usize;
+
+        let group_count = match read_u32(data, &mut pos) {
+            Some(n) => n,
+            None => return false,
+        };
+
+        let mut boat_groups = HashMap::new();
+        let mut groups_by_boat: HashMap<String, HashSet<String>> = HashMap::new();
+
+        for _ in 0..group_count {
+            let group = match read_str(data, &mut pos) {
+                Some(s) => s,
+                None => return false,
+            };
+
+            let member_count = match read_u32(data, &mut pos) {
+                Some(n) => n,
+                None => return false,
+            };
+
+            let mut boats = HashMap::new();
+
+            for _ in 0..member_count {
+                let boat = match read_str(data, &mut pos) {
+                    Some(s) => s,
+                    None => return false,
+                };
+
+                let altname_len = match read_u32(data, &mut pos) {
+                    Some(n) => n,
+                    None => return false,
+                };
+
+                let altname = if altname_len == NONE_SENTINEL {
+                    None
+                } else {
+                    match read_str_of_len(data, &mut pos, altname_len) {
+                        Some(s) => Some(s),
+                        None => return false,
+                    }
+                };
+
+                groups_by_boat.entry(boat.clone()).or_insert_with(HashSet::new).insert(group.clone());
+                boats.insert(boat, altname);
+            }
+
+            boat_groups.insert(group, boats);
+        }
+
+        self.boat_groups = boat_groups;
+        self.groups_by_boat = groups_by_boat;
+        true
+    }
+}
+
+fn write_str(buf: &mut Vec<u8>, s: &str) {
+    let bytes = s.as_bytes();
+    buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    buf.extend_from_slice(bytes);
+}
+
+fn read_u32(data: &[u8], pos: &mut usize) -> Option<u32> {
+    if *pos + 4 > data.len() {
+        return None;
+    }
+    let n = u32::from_le_bytes(data[*pos..*pos + 4].try_into().ok()?);
+    *pos += 4;
+    Some(n)
+}
+
+fn read_str(data: &[u8], pos: &mut usize) -> Option<String> {
+    let len = read_u32(data, pos)?;
+    read_str_of_len(data, pos, len)
+}
+
+fn read_str_of_len(data: &[u8], pos: &mut usize, len: u32) -> Option<String> {
+    let len = len as usize;
+    if *pos + len > data.len() {
+        return None;
+    }
+    let s = String::from(std::str::from_utf8(&data[*pos..*pos + len]).ok()?);
+    *pos += len;
+    Some(s)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_deserialize_round_trips_groups_and_altnames() {
+        let mut br = BoatRegistry::new();
+
+        br.add_boat_to_group(String::from("racefleet"), String::from("boatA"), Some(String::from("Alpha")));
+        br.add_boat_to_group(String::from("racefleet"), String::from("boatB"), None);
+        br.add_boat_to_group(String::from("cruisers"), String::from("boatB"), Some(String::from("Bravo")));
+
+        let blob = br.serialize_groups();
+
+        let mut restored = BoatRegistry::new();
+        assert!(restored.deserialize_groups(&blob));
+
+        assert_eq!(restored.produce_group_membership_response(&String::from("cruisers")), br.produce_group_membership_response(&String::from("cruisers")));
+
+        let mut groups: Vec<String> = restored.produce_boat_groups_response(&String::from("boatB")).lines().map(String::from).collect();
+        groups.sort();
+        assert_eq!(groups, vec![String::from("cruisers"), String::from("racefleet")]);
+    }
+
+    #[test]
+    fn deserialize_empty_blob_yields_empty_topology() {
+        let mut br = BoatRegistry::new();
+        br.add_boat_to_group(String::from("racefleet"), String::from("boatA"), None);
+
+        // An empty topology (zero groups) still round-trips and clears whatever was there.
+        let empty = BoatRegistry::new().serialize_groups();
+        assert!(br.deserialize_groups(&empty));
+
+        assert_eq!(br.produce_group_membership_response(&String::from("racefleet")), String::from(""));
+        assert_eq!(br.produce_boat_groups_response(&String::from("boatA")), String::from(""));
+    }
+
+    #[test]
+    fn deserialize_rejects_truncated_blob() {
+        let mut br = BoatRegistry::new();
+        br.add_boat_to_group(String::from("racefleet"), String::from("boatA"), None);
+
+        let mut blob = br.serialize_groups();
+        blob.truncate(blob.len() - 1);
+
+        // A malformed blob is rejected, and the registry's existing topology is untouched.
+        assert!(!br.deserialize_groups(&blob));
+        assert_eq!(br.produce_boat_groups_response(&String::from("boatA")), String::from("racefleet\n"));
+    }
+
+    #[test]
+    fn produce_boat_groups_response_reflects_all_groups_a_boat_belongs_to() {
+        let mut br = BoatRegistry::new();
+
+        br.add_boat_to_group(String::from("racefleet"), String::from("boatA"), None);
+        br.add_boat_to_group(String::from("cruisers"), String::from("boatA"), None);
+
+        let mut groups: Vec<String> = br.produce_boat_groups_response(&String::from("boatA")).lines().map(String::from).collect();
+        groups.sort();
+        assert_eq!(groups, vec![String::from("cruisers"), String::from("racefleet")]);
+    }
+
+    #[test]
+    fn remove_boat_from_group_clears_boat_from_reverse_index_but_leaves_other_groups() {
+        let mut br = BoatRegistry::new();
+
+        br.add_boat_to_group(String::from("racefleet"), String::from("boatA"), None);
+        br.add_boat_to_group(String::from("cruisers"), String::from("boatA"), None);
+
+        br.remove_boat_from_group(&String::from("racefleet"), &String::from("boatA"));
+
+        assert_eq!(br.produce_boat_groups_response(&String::from("boatA")), String::from("cruisers\n"));
+        assert_eq!(br.produce_group_membership_response(&String::from("racefleet")), String::from(""));
+    }
+
+    #[test]
+    fn remove_boat_from_group_drops_boat_from_reverse_index_entirely_once_it_is_in_no_groups() {
+        let mut br = BoatRegistry::new();
+
+        br.add_boat_to_group(String::from("racefleet"), String::from("boatA"), None);
+        br.remove_boat_from_group(&String::from("racefleet"), &String::from("boatA"));
+
+        assert_eq!(br.produce_boat_groups_response(&String::from("boatA")), String::from(""));
+    }
+
+    #[test]
+    fn remove_boat_cleans_up_every_group_it_was_a_member_of() {
+        let mut br = BoatRegistry::new();
+
+        br.add_boat(1 as *mut c_void, String::from("boatA"));
+        br.add_boat_to_group(String::from("racefleet"), String::from("boatA"), None);
+        br.add_boat_to_group(String::from("cruisers"), String::from("boatA"), Some(String::from("Alpha")));
+        br.add_boat_to_group(String::from("cruisers"), String::from("boatB"), None);
+
+        br.remove_boat(&String::from("boatA"));
+
+        assert_eq!(br.produce_boat_groups_response(&String::from("boatA")), String::from(""));
+        // "racefleet" had only boatA in it, so removing boatA should drop the now-empty group entirely.
+        assert_eq!(br.produce_group_membership_response(&String::from("racefleet")), String::from(""));
+        // "cruisers" still has boatB, so it should survive with boatA gone from it.
+        assert_eq!(br.produce_group_membership_response(&String::from("cruisers")), String::from("boatB,!\n"));
+    }
 }
@@ -0,0 +1,154 @@
+/**
+ * Copyright (C) 2025 ls4096 <ls4096@8bitbyte.ca>
+ *
+ * This program is free software: you can redistribute it and/or modify it
+ * under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, version 3.
+ *
+ * This program is distributed in the hope that it will be useful, but WITHOUT
+ * ANY WARRANTY; without even the implied warranty of MERCHANTABILITY or
+ * FITNESS FOR A PARTICULAR PURPOSE. See the GNU General Public License for
+ * more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Magnetic declination (the angle between true north and magnetic north, positive east) on
+// a coarse 10-degree world grid, derived from a simple dipole approximation of the
+// geomagnetic pole. It's nowhere near as accurate as a real-world magnetic model (e.g.
+// WMM), but it's enough to let boat state output optionally report magnetic headings
+// instead of only true ones.
+
+const LAT_MIN: f64 = -90.0;
+const LAT_STEP: f64 = 10.0;
+const LAT_COUNT: usize = 19; // -90, -80, ..., 90
+
+const LON_MIN: f64 = -180.0;
+const LON_STEP: f64 = 10.0;
+const LON_COUNT: usize = 36; // -180, -170, ..., 170 (180 wraps back to -180)
+
+// Declination in degrees (positive east), indexed [lat_row][lon_col].
+const TABLE: [[f64; LON_COUNT]; LAT_COUNT] = [
+    [0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00], // lat -90
+    [51.42, 46.87, 42.22, 37.51, 32.75, 27.95, 23.12, 18.26, 13.38, 8.49, 3.59, -1.31, -6.22, -11.11, -16.00, -20.86, -25.71, -30.53, -35.31, -40.05, -44.72, -49.32, -53.82, -58.15, -62.23, -65.88, -68.62, -68.97, -58.57, 33.40, 66.85, 69.26, 67.31, 64.00, 60.08, 55.85], // lat -80
+    [27.97, 26.87, 25.22, 23.13, 20.72, 18.04, 15.15, 12.11, 8.96, 5.72, 2.42, -0.89, -4.19, -7.46, -10.66, -13.76, -16.72, -19.50, -22.05, -24.30, -26.16, -27.54, -28.28, -28.20, -27.04, -24.51, -20.28, -14.16, -6.35, 2.35, 10.71, 17.67, 22.77, 26.06, 27.81, 28.36], // lat -70
+    [18.96, 18.72, 17.98, 16.82, 15.31, 13.51, 11.47, 9.24, 6.88, 4.41, 1.87, -0.69, -3.24, -5.74, -8.16, -10.46, -12.59, -14.51, -16.16, -17.49, -18.44, -18.92, -18.85, -18.14, -16.70, -14.48, -11.44, -7.67, -3.35, 1.23, 5.72, 9.78, 13.17, 15.77, 17.57, 18.61], // lat -60
+    [14.56, 14.61, 14.23, 13.47, 12.39, 11.03, 9.44, 7.65, 5.72, 3.67, 1.56, -0.57, -2.70, -4.78, -6.77, -8.63, -10.32, -11.79, -13.01, -13.92, -14.48, -14.64, -14.34, -13.56, -12.25, -10.43, -8.10, -5.36, -2.32, 0.85, 3.98, 6.88, 9.41, 11.47, 13.02, 14.04], // lat -50
+    [12.08, 12.24, 12.04, 11.50, 10.66, 9.56, 8.22, 6.70, 5.02, 3.24, 1.38, -0.51, -2.38, -4.20, -5.94, -7.53, -8.96, -10.18, -11.15, -11.83, -12.19, -12.20, -11.83, -11.06, -9.89, -8.33, -6.42, -4.22, -1.82, 0.67, 3.12, 5.43, 7.48, 9.21, 10.57, 11.52], // lat -40
+    [10.58, 10.81, 10.71, 10.30, 9.60, 8.65, 7.48, 6.12, 4.60, 2.97, 1.27, -0.47, -2.19, -3.86, -5.43, -6.87, -8.14, -9.19, -10.01, -10.55, -10.80, -10.73, -10.32, -9.57, -8.50, -7.11, -5.44, -3.56, -1.53, 0.56, 2.63, 4.59, 6.37, 7.89, 9.12, 10.02], // lat -30
+    [9.67, 9.93, 9.90, 9.58, 8.98, 8.13, 7.06, 5.79, 4.37, 2.82, 1.21, -0.44, -2.08, -3.66, -5.15, -6.49, -7.66, -8.62, -9.33, -9.79, -9.96, -9.83, -9.40, -8.67, -7.65, -6.36, -4.86, -3.17, -1.36, 0.50, 2.34, 4.09, 5.69, 7.08, 8.23, 9.09], // lat -20
+    [9.15, 9.45, 9.47, 9.21, 8.67, 7.89, 6.87, 5.66, 4.28, 2.77, 1.19, -0.44, -2.04, -3.59, -5.04, -6.33, -7.44, -8.34, -8.99, -9.38, -9.49, -9.32, -8.87, -8.14, -7.15, -5.92, -4.50, -2.93, -1.26, 0.46, 2.16, 3.79, 5.29, 6.61, 7.71, 8.56], // lat -10
+    [8.93, 9.28, 9.34, 9.13, 8.64, 7.89, 6.90, 5.70, 4.32, 2.81, 1.20, -0.44, -2.07, -3.63, -5.08, -6.37, -7.46, -8.32, -8.93, -9.28, -9.34, -9.13, -8.64, -7.89, -6.90, -5.70, -4.32, -2.81, -1.20, 0.44, 2.07, 3.63, 5.08, 6.37, 7.46, 8.32], // lat 0
+    [8.99, 9.38, 9.49, 9.32, 8.87, 8.14, 7.15, 5.92, 4.50, 2.93, 1.26, -0.46, -2.16, -3.79, -5.29, -6.61, -7.71, -8.56, -9.15, -9.45, -9.47, -9.21, -8.67, -7.89, -6.87, -5.66, -4.28, -2.77, -1.19, 0.44, 2.04, 3.59, 5.04, 6.33, 7.44, 8.34], // lat 10
+    [9.33, 9.79, 9.96, 9.83, 9.40, 8.67, 7.65, 6.36, 4.86, 3.17, 1.36, -0.50, -2.34, -4.09, -5.69, -7.08, -8.23, -9.09, -9.67, -9.93, -9.90, -9.58, -8.98, -8.13, -7.06, -5.79, -4.37, -2.82, -1.21, 0.44, 2.08, 3.66, 5.15, 6.49, 7.66, 8.62], // lat 20
+    [10.01, 10.55, 10.80, 10.73, 10.32, 9.57, 8.50, 7.11, 5.44, 3.56, 1.53, -0.56, -2.63, -4.59, -6.37, -7.89, -9.12, -10.02, -10.58, -10.81, -10.71, -10.30, -9.60, -8.65, -7.48, -6.12, -4.60, -2.97, -1.27, 0.47, 2.19, 3.86, 5.43, 6.87, 8.14, 9.19], // lat 30
+    [11.15, 11.83, 12.19, 12.20, 11.83, 11.06, 9.89, 8.33, 6.42, 4.22, 1.82, -0.67, -3.12, -5.43, -7.48, -9.21, -10.57, -11.52, -12.08, -12.24, -12.04, -11.50, -10.66, -9.56, -8.22, -6.70, -5.02, -3.24, -1.38, 0.51, 2.38, 4.20, 5.94, 7.53, 8.96, 10.18], // lat 40
+    [13.01, 13.92, 14.48, 14.64, 14.34, 13.56, 12.25, 10.43, 8.10, 5.36, 2.32, -0.85, -3.98, -6.88, -9.41, -11.47, -13.02, -14.04, -14.56, -14.61, -14.23, -13.47, -12.39, -11.03, -9.44, -7.65, -5.72, -3.67, -1.56, 0.57, 2.70, 4.78, 6.77, 8.63, 10.32, 11.79], // lat 50
+    [16.16, 17.49, 18.44, 18.92, 18.85, 18.14, 16.70, 14.48, 11.44, 7.67, 3.35, -1.23, -5.72, -9.78, -13.17, -15.77, -17.57, -18.61, -18.96, -18.72, -17.98, -16.82, -15.31, -13.51, -11.47, -9.24, -6.88, -4.41, -1.87, 0.69, 3.24, 5.74, 8.16, 10.46, 12.59, 14.51], // lat 60
+    [22.05, 24.30, 26.16, 27.54, 28.28, 28.20, 27.04, 24.51, 20.28, 14.16, 6.35, -2.35, -10.71, -17.67, -22.77, -26.06, -27.81, -28.36, -27.97, -26.87, -25.22, -23.13, -20.72, -18.04, -15.15, -12.11, -8.96, -5.72, -2.42, 0.89, 4.19, 7.46, 10.66, 13.76, 16.72, 19.50], // lat 70
+    [35.31, 40.05, 44.72, 49.32, 53.82, 58.15, 62.23, 65.88, 68.62, 68.97, 58.57, -33.40, -66.85, -69.26, -67.31, -64.00, -60.08, -55.85, -51.42, -46.87, -42.22, -37.51, -32.75, -27.95, -23.12, -18.26, -13.38, -8.49, -3.59, 1.31, 6.22, 11.11, 16.00, 20.86, 25.71, 30.53], // lat 80
+    [0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00, 0.00], // lat 90
+];
+
+// Equivalent to `deg.rem_euclid(360.0)`, spelled out with just `%` and a comparison since
+// `rem_euclid` isn't available in `core` and this module (like the physics under
+// `advanced_boats`) isn't gated behind the `std` feature.
+fn normalize_degrees(deg: f64) -> f64 {
+    let r = deg % 360.0;
+    if r < 0.0 {
+        r + 360.0
+    } else {
+        r
+    }
+}
+
+// Bilinearly interpolates the declination (degrees, positive east) at `lat`/`lon` from the
+// surrounding four grid cells. `lat` is clamped to +/-90 and `lon` wraps across +/-180.
+pub fn declination(lat: f64, lon: f64) -> f64 {
+    let lat = lat.max(-90.0).min(90.0);
+    let lon = normalize_degrees(lon + 180.0) - 180.0;
+
+    let lat_f = (lat - LAT_MIN) / LAT_STEP;
+    let lat_row = (lat_f as usize).min(LAT_COUNT - 2);
+    let lat_frac = lat_f - (lat_row as f64);
+
+    let lon_f = (lon - LON_MIN) / LON_STEP;
+    let lon_col = (lon_f as usize) % LON_COUNT;
+    let lon_col_next = (lon_col + 1) % LON_COUNT;
+    let lon_frac = lon_f - (lon_col as f64);
+
+    let v00 = TABLE[lat_row][lon_col];
+    let v01 = TABLE[lat_row][lon_col_next];
+    let v10 = TABLE[lat_row + 1][lon_col];
+    let v11 = TABLE[lat_row + 1][lon_col_next];
+
+    let v0 = v00 * (1.0 - lon_frac) + v01 * lon_frac;
+    let v1 = v10 * (1.0 - lon_frac) + v11 * lon_frac;
+
+    v0 * (1.0 - lat_frac) + v1 * lat_frac
+}
+
+// Converts a true heading to magnetic: `magnetic = true - declination`.
+pub fn true_to_magnetic(heading: f64, lat: f64, lon: f64) -> f64 {
+    normalize_degrees(heading - declination(lat, lon))
+}
+
+// Converts a magnetic heading to true: `true = magnetic + declination`.
+pub fn magnetic_to_true(heading: f64, lat: f64, lon: f64) -> f64 {
+    normalize_degrees(heading + declination(lat, lon))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eq_f64(a: f64, b: f64) -> bool {
+        (a - b).abs() < 0.0001
+    }
+
+    #[test]
+    fn declination_matches_grid_points_exactly() {
+        assert!(eq_f64(TABLE[9][18], declination(0.0, 0.0)));
+        assert!(eq_f64(TABLE[13][20], declination(40.0, 20.0)));
+        assert!(eq_f64(TABLE[5][3], declination(-40.0, -150.0)));
+    }
+
+    #[test]
+    fn declination_interpolates_between_grid_points() {
+        let lo = declination(0.0, 0.0);
+        let hi = declination(10.0, 0.0);
+        let mid = declination(5.0, 0.0);
+
+        // A halfway point between two grid rows should land halfway between their values.
+        assert!(eq_f64(mid, (lo + hi) / 2.0));
+    }
+
+    #[test]
+    fn declination_wraps_longitude_across_the_antimeridian() {
+        assert!(eq_f64(declination(30.0, 180.0), declination(30.0, -180.0)));
+        assert!(eq_f64(declination(30.0, 185.0), declination(30.0, -175.0)));
+    }
+
+    #[test]
+    fn declination_clamps_near_the_poles() {
+        assert!(eq_f64(declination(90.0, 45.0), declination(95.0, 45.0)));
+        assert!(eq_f64(declination(-90.0, 45.0), declination(-100.0, 45.0)));
+    }
+
+    #[test]
+    fn true_and_magnetic_conversions_are_inverses() {
+        let lat = 51.3;
+        let lon = -4.2;
+
+        let true_heading = 123.4;
+        let magnetic = true_to_magnetic(true_heading, lat, lon);
+        assert!(eq_f64(true_heading, magnetic_to_true(magnetic, lat, lon)));
+
+        // Crossing 0/360 must still round-trip correctly.
+        let true_heading = 2.0;
+        let magnetic = true_to_magnetic(true_heading, lat, lon);
+        assert!(eq_f64(true_heading, magnetic_to_true(magnetic, lat, lon)));
+    }
+}
@@ -1,4 +1,4 @@
-/**
+/*
  * Copyright (C) 2021-2022 ls4096 <ls4096@8bitbyte.ca>
  *
  * This program is free software: you can redistribute it and/or modify it
@@ -14,14 +14,44 @@
  * along with this program. If not, see <https://www.gnu.org/licenses/>.
  */
 
+#![cfg_attr(not(feature = "std"), no_std)]
+#![feature(portable_simd)]
+
+mod advanced_boats;
+mod declination;
+
+#[cfg(feature = "std")]
 mod boat_registry;
 
+#[cfg(feature = "std")]
 use std::ffi::{CStr, CString};
+#[cfg(feature = "std")]
 use std::os::raw::{c_char, c_void};
 
+#[cfg(feature = "std")]
 use boat_registry::{ BoatRegistry, BoatRegistryIter };
 
 
+// No `std` needed for any of these: the declination grid and its interpolation are plain
+// arithmetic over a const table.
+
+#[no_mangle]
+pub extern fn sailnavsim_rustlib_declination(lat: f64, lon: f64) -> f64 {
+    declination::declination(lat, lon)
+}
+
+#[no_mangle]
+pub extern fn sailnavsim_rustlib_true_to_magnetic(heading: f64, lat: f64, lon: f64) -> f64 {
+    declination::true_to_magnetic(heading, lat, lon)
+}
+
+#[no_mangle]
+pub extern fn sailnavsim_rustlib_magnetic_to_true(heading: f64, lat: f64, lon: f64) -> f64 {
+    declination::magnetic_to_true(heading, lat, lon)
+}
+
+
+#[cfg(feature = "std")]
 #[no_mangle]
 pub extern fn sailnavsim_rustlib_boatregistry_new() -> *mut c_void {
     let br = Box::new(BoatRegistry::new());
@@ -29,12 +59,14 @@ pub extern fn sailnavsim_rustlib_boatregistry_new() -> *mut c_void {
     ptr as *mut c_void
 }
 
+#[cfg(feature = "std")]
 #[no_mangle]
 pub unsafe extern fn sailnavsim_rustlib_boatregistry_free(ptr_raw: *mut c_void) {
     let _ptr = Box::from_raw(ptr_raw as *mut BoatRegistry);
 }
 
 
+#[cfg(feature = "std")]
 #[no_mangle]
 pub extern fn sailnavsim_rustlib_boatregistry_add_boat_entry(boat_registry_raw: *mut c_void, boat_entry: *mut c_void, boat_name_raw: *const c_char) -> i32 {
     let mut boat_registry = unsafe {
@@ -59,6 +91,7 @@ pub extern fn sailnavsim_rustlib_boatregistry_add_boat_entry(boat_registry_raw:
     result
 }
 
+#[cfg(feature = "std")]
 #[no_mangle]
 pub extern fn sailnavsim_rustlib_boatregistry_get_boat_entry(boat_registry_raw: *mut c_void, boat_name_raw: *const c_char) -> *mut c_void {
     let mut boat_registry = unsafe {
@@ -80,6 +113,7 @@ pub extern fn sailnavsim_rustlib_boatregistry_get_boat_entry(boat_registry_raw:
     result
 }
 
+#[cfg(feature = "std")]
 #[no_mangle]
 pub extern fn sailnavsim_rustlib_boatregistry_remove_boat_entry(boat_registry_raw: *mut c_void, boat_name_raw: *const c_char) -> *mut c_void {
     let mut boat_registry = unsafe {
@@ -102,6 +136,7 @@ pub extern fn sailnavsim_rustlib_boatregistry_remove_boat_entry(boat_registry_ra
 }
 
 
+#[cfg(feature = "std")]
 #[no_mangle]
 pub extern fn sailnavsim_rustlib_boatregistry_get_boats_iterator(boat_registry_raw: *mut c_void, boat_count_raw: *mut u32) -> *mut c_void {
     let boat_registry = unsafe {
@@ -121,6 +156,7 @@ pub extern fn sailnavsim_rustlib_boatregistry_get_boats_iterator(boat_registry_r
     iter_ptr
 }
 
+#[cfg(feature = "std")]
 #[no_mangle]
 pub extern fn sailnavsim_rustlib_boatregistry_boats_iterator_get_next(iter_raw: *mut c_void) -> *mut c_void {
     let mut iter = unsafe {
@@ -133,6 +169,7 @@ pub extern fn sailnavsim_rustlib_boatregistry_boats_iterator_get_next(iter_raw:
     next_boat
 }
 
+#[cfg(feature = "std")]
 #[no_mangle]
 pub extern fn sailnavsim_rustlib_boatregistry_boats_iterator_has_next(iter_raw: *mut c_void) -> i32 {
     let iter = unsafe {
@@ -148,12 +185,14 @@ pub extern fn sailnavsim_rustlib_boatregistry_boats_iterator_has_next(iter_raw:
     result
 }
 
+#[cfg(feature = "std")]
 #[no_mangle]
 pub unsafe extern fn sailnavsim_rustlib_boatregistry_free_boats_iterator(iter_raw: *mut c_void) {
     let _to_free = Box::from_raw(iter_raw as *mut BoatRegistryIter);
 }
 
 
+#[cfg(feature = "std")]
 #[no_mangle]
 pub extern fn sailnavsim_rustlib_boatregistry_group_add_boat(boat_registry_raw: *mut c_void, group_raw: *const c_char, boat_raw: *const c_char, boat_altname_raw: *const c_char) -> i32 {
     let mut boat_registry = unsafe {
@@ -200,6 +239,7 @@ pub extern fn sailnavsim_rustlib_boatregistry_group_add_boat(boat_registry_raw:
     result
 }
 
+#[cfg(feature = "std")]
 #[no_mangle]
 pub extern fn sailnavsim_rustlib_boatregistry_group_remove_boat(boat_registry_raw: *mut c_void, group_raw: *const c_char, boat_raw: *const c_char) {
     let mut boat_registry = unsafe {
@@ -230,6 +270,7 @@ pub extern fn sailnavsim_rustlib_boatregistry_group_remove_boat(boat_registry_ra
 }
 
 
+#[cfg(feature = "std")]
 #[no_mangle]
 pub extern fn sailnavsim_rustlib_boatregistry_produce_group_membership_response(boat_registry_raw: *mut c_void, group_raw: *const c_char) -> *mut c_char {
     let boat_registry = unsafe {
@@ -252,7 +293,87 @@ pub extern fn sailnavsim_rustlib_boatregistry_produce_group_membership_response(
     resp
 }
 
+#[cfg(feature = "std")]
 #[no_mangle]
 pub unsafe extern fn sailnavsim_rustlib_boatregistry_free_group_membership_response(resp: *mut c_char) {
     let _to_free = CString::from_raw(resp);
 }
+
+
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern fn sailnavsim_rustlib_boatregistry_produce_boat_groups_response(boat_registry_raw: *mut c_void, boat_raw: *const c_char) -> *mut c_char {
+    let boat_registry = unsafe {
+        Box::from_raw(boat_registry_raw as *mut BoatRegistry)
+    };
+
+    let boat = unsafe {
+        match CStr::from_ptr(boat_raw).to_str() {
+            Ok(s) => String::from(s),
+            Err(_) => String::from(""),
+        }
+    };
+
+    let resp = match CString::new(boat_registry.produce_boat_groups_response(&boat)) {
+        Ok(cs) => cs.into_raw(),
+        Err(_) => 0 as *mut c_char
+    };
+
+    Box::into_raw(boat_registry);
+    resp
+}
+
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern fn sailnavsim_rustlib_boatregistry_free_boat_groups_response(resp: *mut c_char) {
+    let _to_free = CString::from_raw(resp);
+}
+
+
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern fn sailnavsim_rustlib_boatregistry_serialize_groups(boat_registry_raw: *mut c_void, len_raw: *mut u32) -> *mut u8 {
+    let boat_registry = unsafe {
+        Box::from_raw(boat_registry_raw as *mut BoatRegistry)
+    };
+
+    let mut buf = boat_registry.serialize_groups().into_boxed_slice();
+    let ptr = buf.as_mut_ptr();
+
+    if len_raw != 0 as *mut u32 {
+        unsafe {
+            *len_raw = buf.len() as u32;
+        }
+    }
+
+    std::mem::forget(buf);
+
+    Box::into_raw(boat_registry);
+    ptr
+}
+
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern fn sailnavsim_rustlib_boatregistry_free_serialized_groups(buf_raw: *mut u8, len: u32) {
+    let _to_free = Vec::from_raw_parts(buf_raw, len as usize, len as usize);
+}
+
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern fn sailnavsim_rustlib_boatregistry_deserialize_groups(boat_registry_raw: *mut c_void, buf_raw: *const u8, len: u32) -> i32 {
+    let mut boat_registry = unsafe {
+        Box::from_raw(boat_registry_raw as *mut BoatRegistry)
+    };
+
+    let data = unsafe {
+        std::slice::from_raw_parts(buf_raw, len as usize)
+    };
+
+    let result = match boat_registry.deserialize_groups(data) {
+        true => 0,
+        false => -1,
+    };
+
+    Box::into_raw(boat_registry);
+    result
+}